@@ -0,0 +1,88 @@
+//! JSON HTTP API served alongside the prometheus `/metrics` endpoint.
+//!
+//! The updater publishes the latest snapshot of every region's stations into a
+//! shared [`SharedStations`] handle, which the handlers below read to answer
+//! structured queries without scraping the prometheus text format.
+
+use std::cmp::Ordering;
+use std::sync::Arc;
+
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, StatusCode},
+    response::IntoResponse,
+    routing::get,
+    Json, Router,
+};
+
+use crate::gpx;
+use serde::Deserialize;
+use tokio::sync::RwLock;
+
+use tanker_price::TankerStation;
+
+/// Latest price snapshot shared between the updater and the HTTP handlers.
+pub type SharedStations = Arc<RwLock<Vec<TankerStation>>>;
+
+/// Build the JSON API router with its shared state wired in.
+pub fn router(state: SharedStations) -> Router {
+    Router::new()
+        .route("/api/stations", get(stations))
+        .route("/api/stations/:id", get(station_by_id))
+        .route("/api/cheapest", get(cheapest))
+        .route("/stations.gpx", get(stations_gpx))
+        .with_state(state)
+}
+
+/// `GET /api/stations` – the full current snapshot.
+async fn stations(State(state): State<SharedStations>) -> Json<Vec<TankerStation>> {
+    Json(state.read().await.clone())
+}
+
+/// `GET /api/stations/:id` – a single station by its Tankerkönig ID.
+async fn station_by_id(
+    State(state): State<SharedStations>,
+    Path(id): Path<String>,
+) -> impl IntoResponse {
+    match state.read().await.iter().find(|station| station.id == id) {
+        Some(station) => Json(station.clone()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
+
+/// `GET /stations.gpx` – every station as a GPX waypoint.
+async fn stations_gpx(State(state): State<SharedStations>) -> impl IntoResponse {
+    let body = gpx::stations_to_gpx(&state.read().await);
+    ([(header::CONTENT_TYPE, "application/gpx+xml")], body)
+}
+
+/// Query parameters for [`cheapest`].
+#[derive(Debug, Deserialize)]
+struct CheapestQuery {
+    /// Fuel type to look for (e.g. `e5`, `e10`, `diesel`).
+    fuel: String,
+}
+
+/// `GET /api/cheapest?fuel=e10` – the single cheapest open station for a fuel.
+async fn cheapest(
+    State(state): State<SharedStations>,
+    Query(query): Query<CheapestQuery>,
+) -> impl IntoResponse {
+    let fuel = query.fuel.to_lowercase();
+    let stations = state.read().await;
+    let best = stations
+        .iter()
+        .filter(|station| station.is_open)
+        .filter_map(|station| {
+            station
+                .prices
+                .iter()
+                .find(|price| price.fuel_type.to_string().to_lowercase() == fuel)
+                .map(|price| (station, price.price))
+        })
+        .min_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(Ordering::Equal));
+    match best {
+        Some((station, _)) => Json(station.clone()).into_response(),
+        None => StatusCode::NOT_FOUND.into_response(),
+    }
+}
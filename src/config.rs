@@ -0,0 +1,76 @@
+//! Configuration file handling for the exporter.
+//!
+//! The exporter can monitor several named search regions at once, each with its
+//! own location string and radius, and remap raw Tankerkönig station IDs to
+//! friendly display names via a `[nicknames]` table.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use serde::Deserialize;
+
+/// Default search radius (in km) used when a region omits it.
+fn default_radius() -> f64 {
+    2.
+}
+
+/// The whole exporter configuration as read from a TOML file.
+#[derive(Debug, Deserialize, Default)]
+pub struct Config {
+    /// Named search regions, keyed by their display name.
+    #[serde(default)]
+    pub regions: HashMap<String, Region>,
+
+    /// Mapping from Tankerkönig station ID to a friendly display name.
+    #[serde(default)]
+    pub nicknames: HashMap<String, String>,
+}
+
+/// A single named search region.
+#[derive(Debug, Deserialize)]
+pub struct Region {
+    /// Location to search prices for (coordinate or name to resolve).
+    pub location: String,
+
+    /// Radius in km around the location.
+    #[serde(default = "default_radius")]
+    pub radius: f64,
+}
+
+impl Config {
+    /// Read and parse a configuration file from `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let raw = std::fs::read_to_string(path)?;
+        let config: Config = toml::from_str(&raw)?;
+        // The CLI `--radius` is range-checked against the Tankerkönig terms;
+        // config-supplied radii have to honor the same contract.
+        for (name, region) in &config.regions {
+            if region.radius < 0. || region.radius > 25. {
+                return Err(ConfigError::InvalidRadius {
+                    region: name.clone(),
+                    radius: region.radius,
+                });
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// Possible errors when loading a configuration file.
+#[derive(Debug, thiserror::Error)]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    #[error("Unable to read the config file: {0}")]
+    IoError(#[from] std::io::Error),
+    /// The configuration file could not be parsed.
+    #[error("Unable to parse the config file: {0}")]
+    ParseError(#[from] toml::de::Error),
+    /// A region specified a radius outside the range allowed by the API.
+    #[error("Region {region} has radius {radius}, which must be between 0 and 25km")]
+    InvalidRadius {
+        /// Name of the offending region.
+        region: String,
+        /// The rejected radius.
+        radius: f64,
+    },
+}
@@ -0,0 +1,44 @@
+//! GPX waypoint export for the monitored stations.
+//!
+//! Each station becomes a `<wpt>` carrying its coordinates, a `<name>` built
+//! from brand and station name, and a `<desc>` listing the current fuel prices,
+//! so the monitored stations can be loaded straight into mapping tools.
+
+use std::fmt::Write;
+
+use tanker_price::TankerStation;
+
+/// Escape the characters that are not allowed in XML text content.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Serialize `stations` to a GPX 1.1 document with one `<wpt>` per station.
+pub fn stations_to_gpx(stations: &[TankerStation]) -> String {
+    let mut out = String::new();
+    out.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    out.push_str(
+        "<gpx version=\"1.1\" creator=\"tanker_price\" xmlns=\"http://www.topografix.com/GPX/1/1\">\n",
+    );
+    for station in stations {
+        let prices = station
+            .prices
+            .iter()
+            .map(|price| price.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        // Writing into a String is infallible, so the result can be ignored.
+        let _ = write!(
+            out,
+            "  <wpt lat=\"{}\" lon=\"{}\">\n    <name>{}</name>\n    <desc>{}</desc>\n  </wpt>\n",
+            station.location.lat,
+            station.location.lng,
+            escape(&format!("{}/{}", station.brand, station.name)),
+            escape(&prices),
+        );
+    }
+    out.push_str("</gpx>\n");
+    out
+}
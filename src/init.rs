@@ -0,0 +1,40 @@
+//! The `init` subcommand, which writes a commented starter config file.
+
+use std::path::PathBuf;
+
+/// A commented starter config covering every supported section.
+const STARTER_CONFIG: &str = r#"# tanker_price configuration
+#
+# Define one or more named search regions. Each region has a location (a
+# coordinate or a name resolved via Nominatim) and an optional radius in km
+# (defaults to 2, the Tankerkönig maximum is 25).
+[regions.home]
+location = "Berlin"
+radius = 5.0
+
+# [regions.work]
+# location = "52.5200,13.4050"
+# radius = 3.0
+
+# Map raw Tankerkönig station IDs to friendly display names. Matching stations
+# get this name instead of the one reported by the API.
+[nicknames]
+# "51d4b660-a095-1aa0-e100-80009459e03a" = "The cheap one around the corner"
+"#;
+
+/// Arguments for the `init` subcommand
+#[derive(Debug, clap::Args)]
+pub struct InitArgs {
+    /// Path of the config file to write
+    #[clap(short, long, default_value = "config.toml")]
+    output: PathBuf,
+}
+
+/// Run the `init` subcommand.
+pub fn run(args: InitArgs) {
+    if args.output.exists() {
+        panic!("Refusing to overwrite existing file {:?}", args.output);
+    }
+    std::fs::write(&args.output, STARTER_CONFIG).expect("Unable to write config file!");
+    println!("Wrote starter config to {:?}", args.output);
+}
@@ -0,0 +1,38 @@
+#![deny(
+    warnings,
+    unsafe_code,
+    missing_docs,
+    clippy::missing_docs_in_private_items
+)]
+
+//! Reusable bindings for the [Tankerkönig API](https://creativecommons.tankerkoenig.de/)
+//! together with location parsing/resolving against the
+//! [Nominatim openstreetmap.org API](https://nominatim.openstreetmap.org/ui/search.html).
+//!
+//! The crate is split into a library (this module tree) and the `tanker_price`
+//! prometheus exporter binary. Everything needed to query prices lives here, so
+//! bots, dashboards or one-shot tools can build on the API bindings without
+//! pulling in axum or prometheus:
+//!
+//! ```no_run
+//! # async fn doc() -> Result<(), Box<dyn std::error::Error>> {
+//! use std::str::FromStr;
+//! use tanker_price::{Location, TankerKoenig};
+//!
+//! let location = Location::from_str("Berlin")?;
+//! let coordinates = location.resolve_to_coordinates().await?;
+//! let tk = TankerKoenig::new("your-api-key".to_string(), coordinates.into(), 5.);
+//! for station in tk.load_prices().await? {
+//!     println!("{station}");
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod locator;
+pub mod tankerkoenig;
+
+pub use locator::{CoordinateLocation, GeocodeCache, Location, LocationError};
+pub use tankerkoenig::{
+    TankerError, TankerFuelType, TankerKoenig, TankerPrice, TankerStation,
+};
@@ -1,11 +1,41 @@
 //! Allows parsing and resolving locations with the openstreetmap API
 
+use recoord::Coordinate;
 use regex::Regex;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::error::Error;
 use std::fmt;
 use std::num::ParseFloatError;
+use std::path::PathBuf;
 use std::str::FromStr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// Minimum delay between two Nominatim requests, as required by its
+/// [usage policy](https://operations.osmfoundation.org/policies/nominatim/).
+const MIN_REQUEST_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Block until at least [`MIN_REQUEST_INTERVAL`] has passed since the previous
+/// Nominatim request, reserving the next slot for this caller.
+async fn rate_limit() {
+    /// Time at which the most recent request slot was reserved.
+    static LAST_REQUEST: Mutex<Option<Instant>> = Mutex::new(None);
+
+    let wait = {
+        let mut last = LAST_REQUEST.lock().unwrap();
+        let now = Instant::now();
+        let wait = last
+            .and_then(|prev| MIN_REQUEST_INTERVAL.checked_sub(now.duration_since(prev)))
+            .unwrap_or_default();
+        // Reserve our slot so concurrent callers queue up behind us.
+        *last = Some(now + wait);
+        wait
+    };
+    if !wait.is_zero() {
+        tokio::time::sleep(wait).await;
+    }
+}
 
 /// Convert a sexagesimal coordinate to a decimal one.
 fn sexagesimal_to_decimal(degree: f64, minutes: Option<f64>, seconds: Option<f64>) -> f64 {
@@ -57,6 +87,8 @@ pub enum LocationError {
     ReqwestError(reqwest::Error),
     /// The location can't be resolved by the Openstreetmaps API
     Unresolveable,
+    /// There was an error reading or writing the geocode cache
+    CacheError(std::io::Error),
 }
 
 impl Error for LocationError {}
@@ -79,6 +111,12 @@ impl From<reqwest::Error> for LocationError {
     }
 }
 
+impl From<std::io::Error> for LocationError {
+    fn from(err: std::io::Error) -> Self {
+        Self::CacheError(err)
+    }
+}
+
 /// A coordinate location (as returned by the resolve_to_coordinates location)
 ///
 /// You probably don't want to create this, but use the Location struct instead
@@ -96,6 +134,15 @@ impl fmt::Display for CoordinateLocation {
     }
 }
 
+impl From<CoordinateLocation> for Coordinate {
+    fn from(location: CoordinateLocation) -> Self {
+        Coordinate {
+            lat: location.lat,
+            lng: location.long,
+        }
+    }
+}
+
 /// A location which can describe a place either as a coordinate or an abstract location like an address
 #[derive(Debug, Clone)]
 pub enum Location {
@@ -116,6 +163,8 @@ impl Location {
         match self {
             Location::Coordinates(coordinates) => Ok(coordinates.clone()),
             Location::Named(name) => {
+                // Honor the Nominatim usage policy's request rate limit.
+                rate_limit().await;
                 let locations = reqwest::Client::new()
                     .get("https://nominatim.openstreetmap.org/search")
                     .header(reqwest::header::USER_AGENT, "tanker_price")
@@ -226,3 +275,115 @@ impl FromStr for Location {
         Ok(Self::Named(raw_loc.to_owned()))
     }
 }
+
+/// A single cached geocode result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CacheEntry {
+    /// Latitude of the resolved location
+    lat: f64,
+    /// Longitude of the resolved location
+    long: f64,
+    /// Unix time at which this entry was fetched
+    fetched_at: u64,
+}
+
+/// An on-disk cache of resolved locations.
+///
+/// Nominatim's usage policy requires clients to cache results and avoid
+/// re-querying known locations. This cache persists resolved coordinates to a
+/// JSON file keyed by the normalized query string, so repeated runs and
+/// restarts never hit the API for an already-known location. Entries older than
+/// the optional TTL are treated as stale and re-resolved.
+#[derive(Debug)]
+pub struct GeocodeCache {
+    /// Path of the backing JSON file
+    path: PathBuf,
+    /// How long entries stay valid, or [`None`] to keep them forever
+    ttl: Option<Duration>,
+    /// Cached entries keyed by their normalized query string
+    entries: HashMap<String, CacheEntry>,
+}
+
+impl GeocodeCache {
+    /// Open the cache at `path`, loading any existing entries.
+    ///
+    /// A missing file yields an empty cache; `ttl` bounds how long entries are
+    /// considered fresh.
+    pub fn open(path: impl Into<PathBuf>, ttl: Option<Duration>) -> Result<Self, LocationError> {
+        let path = path.into();
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(raw) => serde_json::from_str(&raw)
+                .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => HashMap::new(),
+            Err(err) => return Err(err.into()),
+        };
+        Ok(Self { path, ttl, entries })
+    }
+
+    /// Normalize a query string so differently-cased/spaced inputs share an entry.
+    fn normalize(query: &str) -> String {
+        query.trim().to_uppercase()
+    }
+
+    /// Resolve `location` to coordinates, using (and populating) the cache.
+    ///
+    /// Coordinate locations resolve directly without touching the cache. Named
+    /// locations are looked up in the cache first and only resolved via the API
+    /// on a miss or an expired entry, after which the result is written back to
+    /// disk.
+    pub async fn resolve(
+        &mut self,
+        location: &Location,
+    ) -> Result<CoordinateLocation, LocationError> {
+        let name = match location {
+            Location::Coordinates(coordinates) => return Ok(coordinates.clone()),
+            Location::Named(name) => name,
+        };
+
+        let key = Self::normalize(name);
+        if let Some(entry) = self.entries.get(&key) {
+            if !self.is_expired(entry) {
+                return Ok(CoordinateLocation {
+                    lat: entry.lat,
+                    long: entry.long,
+                });
+            }
+        }
+
+        let coordinates = location.resolve_to_coordinates().await?;
+        self.entries.insert(
+            key,
+            CacheEntry {
+                lat: coordinates.lat,
+                long: coordinates.long,
+                fetched_at: now_unix(),
+            },
+        );
+        self.save()?;
+        Ok(coordinates)
+    }
+
+    /// Has `entry` outlived the configured TTL?
+    fn is_expired(&self, entry: &CacheEntry) -> bool {
+        match self.ttl {
+            None => false,
+            Some(ttl) => now_unix().saturating_sub(entry.fetched_at) > ttl.as_secs(),
+        }
+    }
+
+    /// Persist the current entries to the backing file.
+    fn save(&self) -> Result<(), LocationError> {
+        let raw = serde_json::to_string_pretty(&self.entries)
+            .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+        std::fs::write(&self.path, raw)?;
+        Ok(())
+    }
+}
+
+/// Current Unix time in whole seconds.
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
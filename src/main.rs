@@ -5,14 +5,23 @@
     clippy::missing_docs_in_private_items
 )]
 
-//! Exposes a prometheus exporter for the [Tankerkönig API](https://creativecommons.tankerkoenig.de/)
+//! Prometheus exporter for the [Tankerkönig API](https://creativecommons.tankerkoenig.de/)
 //! which is also able to resolve locations using the [Nominatim openstreetmap.org API](https://nominatim.openstreetmap.org/ui/search.html).
+//!
+//! This is a thin binary on top of the `tanker_price` library, which carries the
+//! actual API bindings and location handling.
 
+use chrono::TimeZone;
+use chrono_tz::Europe::Berlin;
 use log::{error, info};
-use std::{net::SocketAddr, str::FromStr, time::Duration};
+use std::{
+    collections::{HashMap, HashSet},
+    net::SocketAddr,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
 use tokio::time;
 
-use crate::tankerkoenig::TankerKoenig;
 use axum::{
     response::{IntoResponse, Redirect},
     routing::get,
@@ -20,8 +29,14 @@ use axum::{
 };
 use clap::Parser;
 use prometheus::{register_gauge, register_gauge_vec, Encoder, TextEncoder};
-use recoord::Coordinate;
-mod tankerkoenig;
+use tanker_price::{GeocodeCache, Location, TankerFuelType, TankerKoenig};
+
+use crate::config::{Config, Region};
+mod api;
+mod config;
+mod gpx;
+mod init;
+mod query;
 
 /// Validate the update timings
 fn arg_validate_update_time(time: &str) -> Result<u64, String> {
@@ -47,14 +62,37 @@ fn arg_validate_radius(radius: &str) -> Result<f64, String> {
 #[derive(Parser, Debug)]
 #[clap(author, version, about, long_about = None)]
 struct Args {
-    /// Location to search prices for
+    /// Subcommand to run
+    #[clap(subcommand)]
+    command: Command,
+}
+
+/// The available subcommands
+#[derive(clap::Subcommand, Debug)]
+enum Command {
+    /// Run the long-lived prometheus exporter serving `/metrics`
+    Serve(ServeArgs),
+    /// Resolve a location, fetch prices once and print them
+    Query(query::QueryArgs),
+    /// Write a commented starter config file
+    Init(init::InitArgs),
+}
+
+/// Arguments for the `serve` subcommand
+#[derive(clap::Args, Debug)]
+struct ServeArgs {
+    /// Location to search prices for (ignored when a config file defines regions)
     #[clap(short, long, env)]
-    location: String,
+    location: Option<String>,
 
     /// Radius around location to search
     #[clap(short, long, env, default_value_t = 2., parse(try_from_str=arg_validate_radius))]
     radius: f64,
 
+    /// Path to a TOML config file defining search regions and station nicknames
+    #[clap(short, long, env)]
+    config: Option<String>,
+
     /// API Key for the Tankerkönig API
     #[clap(short = 'k', long, env)]
     tankerkoenig_key: String,
@@ -70,6 +108,14 @@ struct Args {
     /// Socket address to bind to for the prometheus endpoint
     #[clap(long, env, default_value = "0.0.0.0:9501")]
     listen: SocketAddr,
+
+    /// Path to the on-disk geocode cache file
+    #[clap(long, env, default_value = "geocode-cache.json")]
+    cache: std::path::PathBuf,
+
+    /// Time-to-live for cached geocode entries in seconds (omit to keep forever)
+    #[clap(long, env)]
+    cache_ttl: Option<u64>,
 }
 
 /// Expose the prometheus metrics
@@ -91,34 +137,76 @@ async fn main() {
     env_logger::init();
     let args = Args::parse();
 
-    let coordinates = if let Ok(coordinates) = Coordinate::from_str(&args.location) {
-        coordinates
-    } else {
-        recoord::resolvers::nominatim::resolve(&args.location)
+    match args.command {
+        Command::Serve(args) => serve(args).await,
+        Command::Query(args) => query::run(args).await,
+        Command::Init(args) => init::run(args),
+    }
+}
+
+/// Run the long-lived prometheus exporter.
+async fn serve(args: ServeArgs) {
+    let config = args
+        .config
+        .as_ref()
+        .map(Config::load)
+        .transpose()
+        .expect("Unable to load config file!")
+        .unwrap_or_default();
+
+    // Assemble the regions to monitor: either the ones from the config file, or
+    // a single implicit "default" region built from the CLI arguments.
+    let mut regions: HashMap<String, Region> = config.regions;
+    if regions.is_empty() {
+        let location = args
+            .location
+            .clone()
+            .expect("Either --location or a config file with regions is required!");
+        regions.insert(
+            "default".to_string(),
+            Region {
+                location,
+                radius: args.radius,
+            },
+        );
+    }
+
+    let mut cache = GeocodeCache::open(&args.cache, args.cache_ttl.map(Duration::from_secs))
+        .expect("Unable to open geocode cache!");
+
+    let mut tankers = Vec::with_capacity(regions.len());
+    for (name, region) in regions {
+        let location = Location::from_str(&region.location).expect("Unable to parse Location!");
+        let coordinates = cache
+            .resolve(&location)
             .await
-            .expect("Unable to resolve Location!")
-    };
+            .expect("Unable to resolve Location!");
+        info!("Region {name} searching at location {coordinates}");
+        tankers.push((
+            name,
+            TankerKoenig::new(args.tankerkoenig_key.clone(), coordinates.into(), region.radius),
+        ));
+    }
+    let nicknames = config.nicknames;
 
-    info!("Searching at location {:?}", coordinates);
-    let tk = TankerKoenig {
-        api_key: args.tankerkoenig_key,
-        radius: args.radius,
-        location: coordinates,
-    };
+    // Shared snapshot feeding the JSON API routes.
+    let stations: api::SharedStations = std::sync::Arc::new(tokio::sync::RwLock::new(Vec::new()));
 
     let (updater_shutdown_tx, updater_shutdown_rx) = tokio::sync::oneshot::channel::<()>();
     let (server_shutdown_tx, server_shutdown_rx) = tokio::sync::oneshot::channel::<()>();
 
+    let updater_stations = stations.clone();
     let updater = tokio::spawn(async move {
         tokio::select! {
-            _ = updater_loop(tk, args.prometheus_namespace, Duration::from_secs(args.update_interval)) => {},
+            _ = updater_loop(tankers, nicknames, updater_stations, args.prometheus_namespace, Duration::from_secs(args.update_interval)) => {},
             _ = updater_shutdown_rx => {info!("Shutting Down Updater")}
         }
     });
 
     let app = Router::new()
         .route("/metrics", get(metrics))
-        .route("/", get(|| async { Redirect::permanent("/metrics") }));
+        .route("/", get(|| async { Redirect::permanent("/metrics") }))
+        .merge(api::router(stations));
 
     info!("Starting Server...");
     let server = axum::Server::bind(&args.listen)
@@ -156,36 +244,46 @@ async fn main() {
     info!("Goodbye");
 }
 
-/// Run this as a loop to regularly update the prometheus metrics
-async fn updater_loop(tk: TankerKoenig, prometheus_namespace: String, update_interval: Duration) {
+/// Run this as a loop to regularly update the prometheus metrics.
+///
+/// Every configured region is polled on each tick and contributes a `region`
+/// label to all gauges. Station IDs found in `nicknames` have their `name`
+/// label replaced by the configured friendly name.
+async fn updater_loop(
+    tankers: Vec<(String, TankerKoenig)>,
+    nicknames: HashMap<String, String>,
+    stations_snapshot: api::SharedStations,
+    prometheus_namespace: String,
+    update_interval: Duration,
+) {
     let fuel_prices = register_gauge_vec!(
         format!("{}_fuel_price", prometheus_namespace),
         "Price of each fuel type",
-        &["name", "brand", "id", "fuel_type"]
+        &["region", "name", "brand", "id", "fuel_type"]
     )
     .unwrap();
     let is_open = register_gauge_vec!(
         format!("{}_is_open", prometheus_namespace),
         "Is gas station currently open?",
-        &["name", "brand", "id"]
+        &["region", "name", "brand", "id"]
     )
     .unwrap();
     let distance = register_gauge_vec!(
         format!("{}_distance_km", prometheus_namespace),
         "Distance from reference point",
-        &["name", "brand", "id"]
+        &["region", "name", "brand", "id"]
     )
     .unwrap();
     let loc_long = register_gauge_vec!(
         format!("{}_location_long", prometheus_namespace),
         "Longitude of station",
-        &["name", "brand", "id"]
+        &["region", "name", "brand", "id"]
     )
     .unwrap();
     let loc_lat = register_gauge_vec!(
         format!("{}_location_lat", prometheus_namespace),
         "Latitude of station",
-        &["name", "brand", "id"]
+        &["region", "name", "brand", "id"]
     )
     .unwrap();
     let last_update = register_gauge!(
@@ -193,47 +291,111 @@ async fn updater_loop(tk: TankerKoenig, prometheus_namespace: String, update_int
         "Last update in seconds"
     )
     .unwrap();
+    let last_change = register_gauge_vec!(
+        format!("{}_fuel_price_last_change_timestamp_seconds", prometheus_namespace),
+        "Unix time of the last observed price change per fuel type",
+        &["region", "name", "brand", "id", "fuel_type"]
+    )
+    .unwrap();
+
+    // The list endpoint gives no change timestamps, so we synthesize them by
+    // diffing successive polls. For every `(station, fuel)` we remember the last
+    // price we saw and the Unix time at which it last changed.
+    let mut price_history: HashMap<(String, TankerFuelType), (f64, i64)> = HashMap::new();
 
     let mut interval = time::interval(update_interval);
     loop {
         interval.tick().await;
-        info!("Fetching prices...");
-        let load_result = tk.load_prices().await;
-        if let Ok(stations) = load_result {
-            last_update.set(
-                std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs_f64(),
-            );
-
-            for station in &stations {
-                is_open
-                    .with_label_values(&[&station.name, &station.brand, &station.id])
-                    .set(if station.is_open { 1. } else { 0. });
-                distance
-                    .with_label_values(&[&station.name, &station.brand, &station.id])
-                    .set(station.dist);
-                loc_lat
-                    .with_label_values(&[&station.name, &station.brand, &station.id])
-                    .set(station.location.lat);
-                loc_long
-                    .with_label_values(&[&station.name, &station.brand, &station.id])
-                    .set(station.location.lng);
-                for price in &station.prices {
-                    fuel_prices
-                        .with_label_values(&[
-                            &station.name,
-                            &station.brand,
-                            &station.id,
-                            &price.fuel_type.to_string(),
-                        ])
-                        .set(price.price);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs() as i64;
+        // Station/fuel combinations observed this tick, used to prune stale
+        // entries for stations that dropped out of every radius.
+        let mut seen = HashSet::new();
+        // Combined snapshot of every region, published for the JSON API.
+        let mut snapshot = Vec::new();
+        // Whether every region polled successfully this tick. A transient error
+        // leaves `seen` incomplete, so we must not prune based on it.
+        let mut all_succeeded = true;
+        for (region, tk) in &tankers {
+            info!("Fetching prices for region {region}...");
+            let load_result = tk.load_prices().await;
+            if let Ok(stations) = load_result {
+                last_update.set(now as f64);
+
+                for station in &stations {
+                    let name = nicknames.get(&station.id).unwrap_or(&station.name);
+                    is_open
+                        .with_label_values(&[region, name, &station.brand, &station.id])
+                        .set(if station.is_open { 1. } else { 0. });
+                    distance
+                        .with_label_values(&[region, name, &station.brand, &station.id])
+                        .set(station.dist);
+                    loc_lat
+                        .with_label_values(&[region, name, &station.brand, &station.id])
+                        .set(station.location.lat);
+                    loc_long
+                        .with_label_values(&[region, name, &station.brand, &station.id])
+                        .set(station.location.lng);
+                    for price in &station.prices {
+                        let fuel_type = price.fuel_type.to_string();
+                        fuel_prices
+                            .with_label_values(&[
+                                region,
+                                name,
+                                &station.brand,
+                                &station.id,
+                                &fuel_type,
+                            ])
+                            .set(price.price);
+
+                        let key = (station.id.clone(), price.fuel_type);
+                        seen.insert(key.clone());
+                        let changed_at = match price_history.get(&key) {
+                            // Price differs from the last observation: record now.
+                            Some((last_price, _)) if (last_price - price.price).abs() > f64::EPSILON => {
+                                info!(
+                                    "Price for {}/{} changed from {:.3} to {:.3} at {}",
+                                    station.id,
+                                    fuel_type,
+                                    last_price,
+                                    price.price,
+                                    Berlin.timestamp_opt(now, 0).unwrap(),
+                                );
+                                now
+                            }
+                            // Unchanged: keep the previously recorded change time.
+                            Some((_, changed_at)) => *changed_at,
+                            // First observation: seed to now without logging a change.
+                            None => now,
+                        };
+                        price_history.insert(key, (price.price, changed_at));
+                        last_change
+                            .with_label_values(&[
+                                region,
+                                name,
+                                &station.brand,
+                                &station.id,
+                                &fuel_type,
+                            ])
+                            .set(changed_at as f64);
+                    }
                 }
+                info!("Update for region {region} done!");
+                snapshot.extend(stations);
+            } else {
+                error!("Update for region {region} failed: {load_result:?}");
+                all_succeeded = false;
             }
-            info!("Update Done!");
-        } else {
-            error!("Update failed: {:?}", load_result);
         }
+        // Forget stations that disappeared so the map doesn't grow unbounded.
+        // Only prune when every region polled successfully; otherwise `seen` is
+        // missing the failed region's stations and we'd drop live history.
+        if all_succeeded {
+            price_history.retain(|key, _| seen.contains(key));
+        }
+        // Publish the fresh snapshot for the JSON API handlers.
+        *stations_snapshot.write().await = snapshot;
     }
 }
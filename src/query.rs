@@ -0,0 +1,218 @@
+//! The one-shot `query` subcommand.
+//!
+//! Resolves a location, fetches the current prices once, prints the stations
+//! sorted by the chosen fuel price and exits. Unlike `serve` it does not start a
+//! server, which makes it handy for scripts and ad-hoc lookups.
+
+use std::str::FromStr;
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::ArgEnum;
+use tanker_price::{GeocodeCache, Location, TankerKoenig, TankerStation};
+
+use crate::{arg_validate_radius, config::Config};
+
+/// Output format for the `query` subcommand
+#[derive(Debug, Clone, Copy, ArgEnum)]
+pub enum OutputFormat {
+    /// Human readable aligned table
+    Table,
+    /// Comma separated values with a header row
+    Csv,
+    /// JSON array of station objects
+    Json,
+}
+
+/// Fuel type to filter and sort by
+#[derive(Debug, Clone, Copy, ArgEnum)]
+pub enum Fuel {
+    /// Fuel with 5% ethanol
+    E5,
+    /// Fuel with 10% ethanol
+    E10,
+    /// Diesel fuel
+    Diesel,
+}
+
+impl Fuel {
+    /// The label this fuel type carries in [`tanker_price::TankerFuelType`].
+    fn label(self) -> &'static str {
+        match self {
+            Fuel::E5 => "E5",
+            Fuel::E10 => "E10",
+            Fuel::Diesel => "Diesel",
+        }
+    }
+}
+
+/// Arguments for the `query` subcommand
+#[derive(Debug, clap::Args)]
+pub struct QueryArgs {
+    /// Location to search prices for
+    #[clap(short, long, env)]
+    location: String,
+
+    /// Radius around location to search
+    #[clap(short, long, env, default_value_t = 2., parse(try_from_str=arg_validate_radius))]
+    radius: f64,
+
+    /// API Key for the Tankerkönig API
+    #[clap(short = 'k', long, env)]
+    tankerkoenig_key: String,
+
+    /// Fuel type to sort and filter by
+    #[clap(short, long, arg_enum, default_value = "e5")]
+    fuel: Fuel,
+
+    /// Output format
+    #[clap(long, arg_enum, default_value = "table")]
+    format: OutputFormat,
+
+    /// Path to a TOML config file to source station nicknames from
+    #[clap(short, long, env)]
+    config: Option<String>,
+
+    /// Path to the on-disk geocode cache file
+    #[clap(long, env, default_value = "geocode-cache.json")]
+    cache: PathBuf,
+
+    /// Time-to-live for cached geocode entries in seconds (omit to keep forever)
+    #[clap(long, env)]
+    cache_ttl: Option<u64>,
+
+    /// Also write the stations as GPX waypoints to this file
+    #[clap(long)]
+    gpx: Option<PathBuf>,
+}
+
+/// Run the `query` subcommand.
+pub async fn run(args: QueryArgs) {
+    let nicknames = args
+        .config
+        .as_ref()
+        .map(Config::load)
+        .transpose()
+        .expect("Unable to load config file!")
+        .map(|config| config.nicknames)
+        .unwrap_or_default();
+
+    let mut cache = GeocodeCache::open(&args.cache, args.cache_ttl.map(Duration::from_secs))
+        .expect("Unable to open geocode cache!");
+    let location = Location::from_str(&args.location).expect("Unable to parse Location!");
+    let coordinates = cache
+        .resolve(&location)
+        .await
+        .expect("Unable to resolve Location!");
+
+    let tk = TankerKoenig::new(args.tankerkoenig_key, coordinates.into(), args.radius);
+    let mut stations = tk.load_prices().await.expect("Unable to load prices!");
+
+    // Stations without the chosen fuel are treated as infinitely expensive so
+    // they sort last and the cheapest match is always on top of the list.
+    stations.sort_by(|a, b| {
+        let price = |station| price_for(station, args.fuel).unwrap_or(f64::INFINITY);
+        price(a)
+            .partial_cmp(&price(b))
+            .unwrap_or(std::cmp::Ordering::Equal)
+    });
+
+    let display_name = |station: &TankerStation| -> String {
+        nicknames
+            .get(&station.id)
+            .cloned()
+            .unwrap_or_else(|| station.name.clone())
+    };
+
+    if let Some(path) = &args.gpx {
+        std::fs::write(path, crate::gpx::stations_to_gpx(&stations))
+            .expect("Unable to write GPX file!");
+    }
+
+    match args.format {
+        OutputFormat::Table => print_table(&stations, args.fuel, display_name),
+        OutputFormat::Csv => print_csv(&stations, args.fuel, display_name),
+        OutputFormat::Json => print_json(&stations, args.fuel, display_name),
+    }
+}
+
+/// Look up the price of `fuel` for `station`, if present.
+fn price_for(station: &TankerStation, fuel: Fuel) -> Option<f64> {
+    station
+        .prices
+        .iter()
+        .find(|price| price.fuel_type.to_string() == fuel.label())
+        .map(|price| price.price)
+}
+
+/// Render the price of `fuel`, or a dash if the station does not offer it.
+fn render_price(station: &TankerStation, fuel: Fuel) -> String {
+    price_for(station, fuel).map_or_else(|| "-".to_string(), |price| format!("{price:.3}"))
+}
+
+/// Print the stations as an aligned table.
+fn print_table(stations: &[TankerStation], fuel: Fuel, name: impl Fn(&TankerStation) -> String) {
+    println!(
+        "{:<30} {:<24} {:>6} {:>8} {:>8}",
+        "STATION",
+        "ID",
+        "OPEN",
+        "DIST",
+        fuel.label()
+    );
+    for station in stations {
+        println!(
+            "{:<30} {:<24} {:>6} {:>8.1} {:>8}",
+            format!("{}/{}", station.brand, name(station)),
+            station.id,
+            if station.is_open { "yes" } else { "no" },
+            station.dist,
+            render_price(station, fuel),
+        );
+    }
+}
+
+/// Quote a CSV field per RFC 4180, escaping embedded quotes.
+fn csv_field(value: &str) -> String {
+    format!("\"{}\"", value.replace('"', "\"\""))
+}
+
+/// Print the stations as CSV with a header row.
+fn print_csv(stations: &[TankerStation], fuel: Fuel, name: impl Fn(&TankerStation) -> String) {
+    println!("brand,name,id,is_open,dist,{}", fuel.label().to_lowercase());
+    for station in stations {
+        println!(
+            "{},{},{},{},{},{}",
+            csv_field(&station.brand),
+            csv_field(&name(station)),
+            csv_field(&station.id),
+            station.is_open,
+            station.dist,
+            render_price(station, fuel),
+        );
+    }
+}
+
+/// Print the stations as a JSON array.
+fn print_json(stations: &[TankerStation], fuel: Fuel, name: impl Fn(&TankerStation) -> String) {
+    let values: Vec<_> = stations
+        .iter()
+        .map(|station| {
+            serde_json::json!({
+                "brand": station.brand,
+                "name": name(station),
+                "id": station.id,
+                "is_open": station.is_open,
+                "dist": station.dist,
+                "lat": station.location.lat,
+                "lng": station.location.lng,
+                "price": price_for(station, fuel),
+            })
+        })
+        .collect();
+    println!(
+        "{}",
+        serde_json::to_string_pretty(&values).expect("Unable to serialize stations")
+    );
+}
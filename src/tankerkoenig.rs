@@ -4,11 +4,26 @@
 //! loading prices from the API.
 
 use recoord::Coordinate;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::fmt::{self, Display};
 
+/// Serialize a [`Coordinate`] as a `{ "lat": .., "lng": .. }` object.
+///
+/// `recoord::Coordinate` does not implement [`Serialize`] itself, so we project
+/// the two fields we care about by hand.
+fn serialize_coordinate<S>(coord: &Coordinate, serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    use serde::ser::SerializeStruct;
+    let mut state = serializer.serialize_struct("Coordinate", 2)?;
+    state.serialize_field("lat", &coord.lat)?;
+    state.serialize_field("lng", &coord.lng)?;
+    state.end()
+}
+
 /// A Tankerkönig station with all required information including prices
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TankerStation {
     /// ID of the station (as provided by the Tankerkönig API)
     pub id: String,
@@ -23,6 +38,7 @@ pub struct TankerStation {
     /// The fuel prices of this station
     pub prices: Vec<TankerPrice>,
     /// The location of this station
+    #[serde(serialize_with = "serialize_coordinate")]
     pub location: Coordinate,
 }
 
@@ -67,7 +83,7 @@ impl From<TankerAPIStation> for TankerStation {
 }
 
 /// Available fuel types
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
 pub enum TankerFuelType {
     /// Fuel with 10% ethanol
     E10,
@@ -98,7 +114,7 @@ impl From<TankerFuelType> for String {
 }
 
 /// A price entry for a single fuel type
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct TankerPrice {
     /// The fuel type of this
     pub fuel_type: TankerFuelType,
@@ -177,6 +193,16 @@ pub struct TankerKoenig {
 }
 
 impl TankerKoenig {
+    /// Create a new TankerKoenig binding for the given API key, search center
+    /// and radius (in km).
+    pub fn new(api_key: String, location: Coordinate, radius: f64) -> Self {
+        Self {
+            api_key,
+            radius,
+            location,
+        }
+    }
+
     /// Load the prices for the current TankerKoenig instance.
     pub async fn load_prices(&self) -> Result<Vec<TankerStation>, TankerError> {
         let client = reqwest::Client::new();